@@ -1,15 +1,25 @@
 use anyhow::Result;
 use clap::Parser;
-use std::{collections::HashMap, fmt, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
 use iroh_gossip::{
     net::{Event, Gossip, GossipEvent, GossipReceiver},
     proto::TopicId,
 };
 use futures_lite::StreamExt;
-use iroh::{protocol::Router, Endpoint, NodeAddr, NodeId};
+use iroh::{
+    protocol::Router, Endpoint, NodeAddr, NodeId, RelayMap, RelayMode, RelayUrl, SecretKey,
+    Signature,
+};
 use serde::{Deserialize, Serialize};
 use chrono::Local;
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -17,6 +27,15 @@ struct Args {
     name: Option<String>,
     #[clap(short, long, default_value = "0")]
     bind_port: u16,
+    /// Hex-encoded ed25519 secret key to reuse a stable node identity across restarts.
+    #[clap(long)]
+    secret_key: Option<String>,
+    /// Use a custom relay server instead of the n0 default.
+    #[clap(long)]
+    relay: Option<String>,
+    /// Run without any relay (e.g. on a LAN).
+    #[clap(long)]
+    no_relay: bool,
     #[clap(subcommand)]
     command: Command,
 }
@@ -46,10 +65,30 @@ async fn main() -> Result<()> {
         }
     };
 
-    let endpoint = Endpoint::builder()
-        .discovery_n0()
-        .bind()
-        .await?;
+    let mut builder = Endpoint::builder();
+
+    builder = match &args.secret_key {
+        Some(hex) => builder.secret_key(parse_secret_key(hex)?),
+        None => {
+            let secret_key = SecretKey::generate(rand::rngs::OsRng);
+            println!(
+                "-> generated secret key: {} (pass --secret-key to reuse this identity)",
+                data_encoding::HEXLOWER.encode(&secret_key.to_bytes())
+            );
+            builder.secret_key(secret_key)
+        }
+    };
+
+    builder = if args.no_relay {
+        builder.relay_mode(RelayMode::Disabled)
+    } else if let Some(url) = &args.relay {
+        let url: RelayUrl = url.parse()?;
+        builder.relay_mode(RelayMode::Custom(RelayMap::from_url(url)))
+    } else {
+        builder.discovery_n0()
+    };
+
+    let endpoint = builder.bind().await?;
 
     println!("-> node id: {}", endpoint.node_id());
     
@@ -81,59 +120,206 @@ async fn main() -> Result<()> {
         }
     };
     
-    let (sender, receiver) = gossip_builder.subscribe_and_join(topic, node_ids).await?.split();
+    let secret_key = endpoint.secret_key().clone();
+    let store = Arc::new(HistoryStore::open("chat-history.db")?);
+    let active = Arc::new(Mutex::new(topic));
+
+    let mut my_name = args.name.clone();
+    // monotonic per-sender sequence shared by all of this node's broadcasts,
+    // resumed from the store so it never rewinds across restarts
+    let my_seq = Arc::new(AtomicU64::new(store.next_seq(&endpoint.node_id())?));
+    let mut rooms: HashMap<TopicId, RoomHandle> = HashMap::new();
+    let handle = join_room(
+        &gossip_builder,
+        &endpoint,
+        &secret_key,
+        &store,
+        &active,
+        &my_seq,
+        topic,
+        node_ids,
+        my_name.clone(),
+    )
+    .await?;
+    rooms.insert(topic, handle);
     println!("-> connected");
 
-    // broadcast our name, if set
-    if let Some(name) = args.name.clone() {
-        let message = Message::AboutMe {
-            from: endpoint.node_id(),
-            name,
-        };
-        sender.broadcast(message.to_vec().into()).await?;
-    }
-
-    let names = Arc::new(Mutex::new(HashMap::new()));
-    let names_clone = Arc::clone(&names);
-
-    tokio::spawn(subscribe_loop(receiver, names_clone));
-
     let (line_tx, mut line_rx) = tokio::sync::mpsc::channel(1);
-    
+
     std::thread::spawn(move || input_loop(line_tx));
 
     println!("-> send a message to broadcast");
 
     while let Some(text) = line_rx.recv().await {
         let text = text.trim().to_string();
+        let active_topic = *active.lock().unwrap();
 
         if text.starts_with("/") {
-            match text.as_str() {
-                "/exit" => {
+            let mut parts = text.split_whitespace();
+            match parts.next() {
+                Some("/exit") => {
                     println!("leaving chat...");
+                    // let every room know we are going before we tear down
+                    let leaving = Message::Leaving {
+                        from: endpoint.node_id(),
+                    };
+                    for room in rooms.values() {
+                        room.sender
+                            .broadcast(SignedMessage::sign_and_encode(&secret_key, &leaving).into())
+                            .await?;
+                    }
                     break;
                 }
-                "/list" => {
-                    let names_guard = names.lock().unwrap();
-
-                    println!("user history: {:?}", names_guard.values().collect::<Vec<_>>());
+                Some("/list") => {
+                    if let Some(room) = rooms.get(&active_topic) {
+                        let names_guard = room.names.lock().unwrap();
+                        println!(
+                            "user history: {:?}",
+                            names_guard.values().collect::<Vec<_>>()
+                        );
+                    }
                 }
-                _ => {
-                    println!("unknown command: {}", text);
+                Some("/history") => {
+                    let n = parts.next().and_then(|n| n.parse::<u64>().ok()).unwrap_or(20);
+                    let names = rooms.get(&active_topic).map(|r| Arc::clone(&r.names));
+                    for stored in store.recent(&active_topic, n)? {
+                        let name = names
+                            .as_ref()
+                            .and_then(|n| n.lock().unwrap().get(&stored.from).cloned())
+                            .unwrap_or_else(|| stored.from.fmt_short());
+                        print_message_at("", stored.at, &name, &stored.text);
+                    }
                 }
+                Some("/who") => {
+                    if let Some(room) = rooms.get(&active_topic) {
+                        let present = room.present.lock().unwrap();
+                        let names_guard = room.names.lock().unwrap();
+                        let members: Vec<String> = present
+                            .iter()
+                            .map(|id| {
+                                names_guard
+                                    .get(id)
+                                    .map_or_else(|| id.fmt_short(), String::to_string)
+                            })
+                            .collect();
+                        println!("-> present: {:?}", members);
+                    }
+                }
+                Some("/nick") => match parts.next() {
+                    Some(nick) => {
+                        let nick = nick.to_string();
+                        let message = Message::AboutMe {
+                            from: endpoint.node_id(),
+                            name: nick.clone(),
+                            at: Local::now().timestamp_millis(),
+                            seq: my_seq.fetch_add(1, Ordering::Relaxed),
+                        };
+                        for room in rooms.values() {
+                            room.sender
+                                .broadcast(
+                                    SignedMessage::sign_and_encode(&secret_key, &message).into(),
+                                )
+                                .await?;
+                        }
+                        let old = my_name.as_deref().unwrap_or("you");
+                        println!("-> {} is now known as {}", old, nick);
+                        my_name = Some(nick);
+                    }
+                    None => println!("-> usage: /nick <name>"),
+                },
+                Some("/rooms") => {
+                    for topic in rooms.keys() {
+                        let marker = if *topic == active_topic { " (active)" } else { "" };
+                        println!("-> {}{}", short_topic(topic), marker);
+                    }
+                }
+                Some("/switch") => match parts.next().and_then(|id| find_room(&rooms, id)) {
+                    Some(target) => {
+                        *active.lock().unwrap() = target;
+                        println!("-> active room is now {}", short_topic(&target));
+                    }
+                    None => println!("-> no such room"),
+                },
+                Some("/part") => match parts.next().and_then(|id| find_room(&rooms, id)) {
+                    Some(target) => {
+                        if let Some(room) = rooms.remove(&target) {
+                            room.task.abort();
+                            println!("-> left room {}", short_topic(&target));
+                        }
+                        if target == active_topic {
+                            if let Some(next) = rooms.keys().next().copied() {
+                                *active.lock().unwrap() = next;
+                                println!("-> active room is now {}", short_topic(&next));
+                            } else {
+                                println!("-> no rooms left; /join a ticket or /exit");
+                            }
+                        }
+                    }
+                    None => println!("-> no such room"),
+                },
+                Some("/join") => match parts.next() {
+                    Some(ticket) => {
+                        let Ticket { topic, nodes } = Ticket::from_str(ticket)?;
+                        if rooms.contains_key(&topic) {
+                            println!("-> already in room {}", short_topic(&topic));
+                            continue;
+                        }
+                        let node_ids = nodes.iter().map(|p| p.node_id).collect();
+                        for node in nodes {
+                            endpoint.add_node_addr(node)?;
+                        }
+                        let handle = join_room(
+                            &gossip_builder,
+                            &endpoint,
+                            &secret_key,
+                            &store,
+                            &active,
+                            &my_seq,
+                            topic,
+                            node_ids,
+                            my_name.clone(),
+                        )
+                        .await?;
+                        rooms.insert(topic, handle);
+                        println!("-> joined room {}", short_topic(&topic));
+                    }
+                    None => println!("-> usage: /join <ticket>"),
+                },
+                _ => println!("unknown command: {}", text),
             }
             continue;
         }
 
+        let Some(room) = rooms.get(&active_topic) else {
+            println!("-> not in any room; /join a ticket first");
+            continue;
+        };
+
+        let at = Local::now().timestamp_millis();
+        let seq = my_seq.fetch_add(1, Ordering::Relaxed);
         let message = Message::Message {
             from: endpoint.node_id(),
             text: text.clone(),
+            at,
+            seq,
         };
 
-        sender.broadcast(message.to_vec().into()).await?;
-
-        let name = args.name.as_ref().map_or_else(|| "you".to_string(), |n| n.clone());
-        print_message(&name, &text);
+        let signed = SignedMessage::sign(&secret_key, &message);
+        room.sender.broadcast(signed.encode().into()).await?;
+
+        store.insert(
+            &active_topic,
+            &StoredMessage {
+                from: endpoint.node_id(),
+                seq,
+                at,
+                text: text.clone(),
+                signature: signed.signature,
+            },
+        )?;
+
+        let name = my_name.as_ref().map_or_else(|| "you".to_string(), |n| n.clone());
+        print_message_at("", at, &name, &text);
     }
 
     router_builder.shutdown().await?;
@@ -141,9 +327,106 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-fn print_message(name: &str, text: &str) {
-    let timestamp = Local::now().format("%H:%M:%S");
-    println!("\x1b[1;32m[{}]\x1b[0m \x1b[1;34m{}:\x1b[0m {}", timestamp, name, text);
+/// A topic the node is currently subscribed to, together with everything needed
+/// to broadcast on it and to tear its receive loop down again.
+struct RoomHandle {
+    sender: iroh_gossip::net::GossipSender,
+    task: tokio::task::JoinHandle<Result<()>>,
+    names: Arc<Mutex<HashMap<NodeId, String>>>,
+    /// Nodes currently present in the room, tracked separately from the
+    /// ever-growing `names` map so leaves can be reflected.
+    present: Arc<Mutex<HashSet<NodeId>>>,
+}
+
+/// Subscribe to `topic`, announce ourselves, ask for backfill, and spawn its
+/// receive loop, returning the handle that owns the room.
+#[allow(clippy::too_many_arguments)]
+async fn join_room(
+    gossip: &Gossip,
+    endpoint: &Endpoint,
+    secret_key: &SecretKey,
+    store: &Arc<HistoryStore>,
+    active: &Arc<Mutex<TopicId>>,
+    my_seq: &Arc<AtomicU64>,
+    topic: TopicId,
+    bootstrap: Vec<NodeId>,
+    name: Option<String>,
+) -> Result<RoomHandle> {
+    let (sender, receiver) = gossip.subscribe_and_join(topic, bootstrap).await?.split();
+    let names = Arc::new(Mutex::new(HashMap::new()));
+    let present = Arc::new(Mutex::new(HashSet::new()));
+
+    if let Some(name) = name {
+        let message = Message::AboutMe {
+            from: endpoint.node_id(),
+            name,
+            at: Local::now().timestamp_millis(),
+            seq: my_seq.fetch_add(1, Ordering::Relaxed),
+        };
+        sender
+            .broadcast(SignedMessage::sign_and_encode(secret_key, &message).into())
+            .await?;
+    }
+
+    // ask peers to replay what we missed before we connected
+    let request = Message::HistoryRequest {
+        from: endpoint.node_id(),
+    };
+    sender
+        .broadcast(SignedMessage::sign_and_encode(secret_key, &request).into())
+        .await?;
+
+    let task = tokio::spawn(subscribe_loop(
+        receiver,
+        sender.clone(),
+        secret_key.clone(),
+        topic,
+        Arc::clone(&names),
+        Arc::clone(&present),
+        Arc::clone(store),
+        Arc::clone(active),
+    ));
+
+    Ok(RoomHandle {
+        sender,
+        task,
+        names,
+        present,
+    })
+}
+
+/// Parse a hex-encoded ed25519 secret key supplied via `--secret-key`.
+fn parse_secret_key(hex: &str) -> Result<SecretKey> {
+    let bytes = data_encoding::HEXLOWER_PERMISSIVE.decode(hex.as_bytes())?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("secret key must be 32 bytes"))?;
+    Ok(SecretKey::from_bytes(&bytes))
+}
+
+/// Short, human-facing form of a topic id used in prompts and prefixes.
+fn short_topic(topic: &TopicId) -> String {
+    topic.to_string().chars().take(8).collect()
+}
+
+/// Resolve a short id typed by the user back to a joined topic.
+fn find_room(rooms: &HashMap<TopicId, RoomHandle>, id: &str) -> Option<TopicId> {
+    rooms
+        .keys()
+        .find(|topic| topic.to_string().starts_with(id))
+        .copied()
+}
+
+fn print_message_at(prefix: &str, at: i64, name: &str, text: &str) {
+    let timestamp = chrono::DateTime::from_timestamp_millis(at)
+        .map(|dt| dt.with_timezone(&Local))
+        .unwrap_or_else(Local::now)
+        .format("%H:%M:%S");
+    println!(
+        "{}\x1b[1;32m[{}]\x1b[0m \x1b[1;34m{}:\x1b[0m {}",
+        prefix, timestamp, name, text
+    );
 }
 
 fn input_loop(line_tx: tokio::sync::mpsc::Sender<String>) -> Result<()> {
@@ -157,26 +440,118 @@ fn input_loop(line_tx: tokio::sync::mpsc::Sender<String>) -> Result<()> {
     }
 }
 
-async fn subscribe_loop(mut receiver: GossipReceiver,
-    names: Arc<Mutex<HashMap<NodeId, String>>>,) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+async fn subscribe_loop(
+    mut receiver: GossipReceiver,
+    sender: iroh_gossip::net::GossipSender,
+    secret_key: SecretKey,
+    topic: TopicId,
+    names: Arc<Mutex<HashMap<NodeId, String>>>,
+    present: Arc<Mutex<HashSet<NodeId>>>,
+    store: Arc<HistoryStore>,
+    active: Arc<Mutex<TopicId>>,
+) -> Result<()> {
+    // prefix incoming lines with the short topic id unless this is the active room
+    let room_prefix = || {
+        if *active.lock().unwrap() == topic {
+            String::new()
+        } else {
+            format!("[{}] ", short_topic(&topic))
+        }
+    };
     while let Some(event) = receiver.try_next().await? {
         if let Event::Gossip(GossipEvent::Received(msg)) = event {
-            match Message::from_bytes(&msg.content)? {
-                Message::AboutMe { from, name } => {
-                    let mut names_guard = names.lock().unwrap();
-                    names_guard.insert(from, name.clone());
-                    println!("-> {} joined chat as {}", from.fmt_short(), name);
+            let (_from, signature, message) = match SignedMessage::decode_and_verify(&msg.content) {
+                Ok(verified) => verified,
+                Err(err) => {
+                    eprintln!("-> dropping unverified message: {err}");
+                    continue;
+                }
+            };
+            match message {
+                Message::AboutMe { from, name, at: _, seq: _ } => {
+                    let previous = names.lock().unwrap().insert(from, name.clone());
+                    let newly_present = present.lock().unwrap().insert(from);
+                    match previous {
+                        // already known under a different name => a /nick change
+                        Some(old) if old != name && !newly_present => {
+                            println!("{}-> {} is now known as {}", room_prefix(), old, name);
+                        }
+                        _ => {
+                            println!(
+                                "{}-> {} joined chat as {}",
+                                room_prefix(),
+                                from.fmt_short(),
+                                name
+                            );
+                        }
+                    }
                     print!("\x07");
                 }
-                Message::Message { from, text } => {
+                Message::Leaving { from } => {
+                    present.lock().unwrap().remove(&from);
+                    let name = names
+                        .lock()
+                        .unwrap()
+                        .get(&from)
+                        .map_or_else(|| from.fmt_short(), String::to_string);
+                    println!("{}-> {} left chat", room_prefix(), name);
+                    print!("\x07");
+                }
+                Message::Message { from, text, at, seq } => {
+                    store.insert(
+                        &topic,
+                        &StoredMessage {
+                            from,
+                            seq,
+                            at,
+                            text: text.clone(),
+                            signature,
+                        },
+                    )?;
+
                     let names_guard = names.lock().unwrap();
                     let name = names_guard
                         .get(&from)
                         .map_or_else(|| from.fmt_short(), String::to_string);
 
-                    print_message(&name, &text);
+                    print_message_at(&room_prefix(), at, &name, &text);
                     print!("\x07");
                 }
+                Message::HistoryRequest { from } => {
+                    let messages = store.recent(&topic, HistoryStore::REPLY_LIMIT)?;
+                    if messages.is_empty() {
+                        continue;
+                    }
+                    let reply = Message::HistoryReply {
+                        from: secret_key.public(),
+                        messages,
+                    };
+                    sender
+                        .broadcast(SignedMessage::sign_and_encode(&secret_key, &reply).into())
+                        .await?;
+                    let _ = from;
+                }
+                Message::HistoryReply { from: _, mut messages } => {
+                    // render a backfilled batch in chronological order
+                    messages.sort_by(|a, b| {
+                        (a.at, a.from.as_bytes(), a.seq).cmp(&(b.at, b.from.as_bytes(), b.seq))
+                    });
+                    let names_guard = names.lock().unwrap();
+                    for stored in messages {
+                        // each replayed line must carry its author's own signature;
+                        // never trust a `from` the replier merely asserts
+                        if stored.verify().is_err() {
+                            continue;
+                        }
+                        if store.insert(&topic, &stored)? {
+                            let name = names_guard
+                                .get(&stored.from)
+                                .map_or_else(|| stored.from.fmt_short(), String::to_string);
+                            print_message_at(&room_prefix(), stored.at, &name, &stored.text);
+                        }
+                    }
+                }
             }
         }
     }
@@ -217,8 +592,11 @@ impl FromStr for Ticket {
 
 #[derive(Debug, Serialize, Deserialize)]
 enum Message {
-    AboutMe { from: NodeId, name: String },
-    Message { from: NodeId, text: String },
+    AboutMe { from: NodeId, name: String, at: i64, seq: u64 },
+    Message { from: NodeId, text: String, at: i64, seq: u64 },
+    HistoryRequest { from: NodeId },
+    HistoryReply { from: NodeId, messages: Vec<StoredMessage> },
+    Leaving { from: NodeId },
 }
 
 impl Message {
@@ -229,4 +607,207 @@ impl Message {
     pub fn to_vec(&self) -> Vec<u8> {
         serde_json::to_vec(self).expect("serde_json::to_vec is infallible")
     }
+
+    fn from(&self) -> NodeId {
+        match self {
+            Message::AboutMe { from, .. } => *from,
+            Message::Message { from, .. } => *from,
+            Message::HistoryRequest { from, .. } => *from,
+            Message::HistoryReply { from, .. } => *from,
+            Message::Leaving { from } => *from,
+        }
+    }
+}
+
+/// A chat line as persisted in the local history database.
+///
+/// `seq` is the per-sender ordinal of the message within a topic; because gossip
+/// delivers a given sender's messages to every peer in order, peers independently
+/// agree on the `(from, seq)` pair, which is what backfill dedupes on. The
+/// sender's `signature` travels with the line so a backfilled batch can be
+/// verified against `from` just like a live envelope, closing the replay path
+/// around message signing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredMessage {
+    from: NodeId,
+    seq: u64,
+    at: i64,
+    text: String,
+    signature: Signature,
+}
+
+impl StoredMessage {
+    /// Verify that `signature` really is `from`'s signature over this line.
+    fn verify(&self) -> Result<()> {
+        let message = Message::Message {
+            from: self.from,
+            text: self.text.clone(),
+            at: self.at,
+            seq: self.seq,
+        };
+        self.from.verify(&message.to_vec(), &self.signature)?;
+        Ok(())
+    }
+}
+
+/// SQLite-backed log of every chat line seen on a topic, used to catch up
+/// late joiners and to serve the `/history` command.
+struct HistoryStore {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+impl HistoryStore {
+    /// Cap on the number of entries returned in a single [`Message::HistoryReply`].
+    const REPLY_LIMIT: u64 = 200;
+
+    fn open(path: &str) -> Result<Self> {
+        let conn = rusqlite::Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS messages (
+                topic TEXT NOT NULL,
+                sender TEXT NOT NULL,
+                seq INTEGER NOT NULL,
+                at INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                signature BLOB NOT NULL,
+                PRIMARY KEY (topic, sender, seq)
+            );",
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The next sequence number this node may safely emit, i.e. one past the
+    /// highest `seq` already persisted for `from` across every topic.
+    ///
+    /// Seeding the in-memory counter from here keeps sequence numbers monotonic
+    /// across restarts, so a node reusing a stable identity never re-emits a
+    /// `(sender, seq)` pair that peers (or its own store) would dedupe away.
+    fn next_seq(&self, from: &NodeId) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        let max: Option<i64> = conn.query_row(
+            "SELECT MAX(seq) FROM messages WHERE sender = ?1",
+            rusqlite::params![from.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(max.map_or(0, |m| m as u64 + 1))
+    }
+
+    /// Insert `message` if the `(from, seq)` pair is not already stored.
+    ///
+    /// Returns `true` when the row was newly inserted, `false` when it was a
+    /// duplicate that was ignored.
+    fn insert(&self, topic: &TopicId, message: &StoredMessage) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let changed = conn.execute(
+            "INSERT OR IGNORE INTO messages (topic, sender, seq, at, text, signature)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                topic.to_string(),
+                message.from.to_string(),
+                message.seq as i64,
+                message.at,
+                message.text,
+                message.signature.to_bytes().to_vec(),
+            ],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// The most recent `limit` stored lines for `topic`, oldest first.
+    fn recent(&self, topic: &TopicId, limit: u64) -> Result<Vec<StoredMessage>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT sender, seq, at, text, signature FROM messages
+             WHERE topic = ?1
+             ORDER BY at DESC, sender DESC, seq DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(
+            rusqlite::params![topic.to_string(), limit as i64],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Vec<u8>>(4)?,
+                ))
+            },
+        )?;
+
+        let mut messages = Vec::new();
+        for row in rows {
+            let (sender, seq, at, text, signature) = row?;
+            let from = NodeId::from_str(&sender)?;
+            let signature = signature_from_bytes(&signature)?;
+            messages.push(StoredMessage {
+                from,
+                seq: seq as u64,
+                at,
+                text,
+                signature,
+            });
+        }
+        messages.reverse();
+        Ok(messages)
+    }
+}
+
+/// Envelope that is actually broadcast on the gossip topic.
+///
+/// The inner [`Message`] is serialized into `data` and signed with the sender's
+/// ed25519 secret key, so peers can reject messages whose `from` has been
+/// forged instead of trusting the deserialized `NodeId` blindly.
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedMessage {
+    from: NodeId,
+    data: Vec<u8>,
+    signature: Signature,
+}
+
+impl SignedMessage {
+    /// Serialize `message` and sign the bytes with `secret_key`.
+    pub fn sign(secret_key: &SecretKey, message: &Message) -> Self {
+        let data = message.to_vec();
+        let signature = secret_key.sign(&data);
+        SignedMessage {
+            from: secret_key.public(),
+            data,
+            signature,
+        }
+    }
+
+    /// Encode the envelope ready to be broadcast on the gossip topic.
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("serde_json::to_vec is infallible")
+    }
+
+    /// Sign `message` and encode it in one step.
+    pub fn sign_and_encode(secret_key: &SecretKey, message: &Message) -> Vec<u8> {
+        Self::sign(secret_key, message).encode()
+    }
+
+    /// Decode an envelope and verify its signature against the embedded `from`.
+    ///
+    /// Returns an error (so the caller drops the message) if the signature does
+    /// not verify or if the inner message's `from` disagrees with the wrapper's.
+    pub fn decode_and_verify(bytes: &[u8]) -> Result<(NodeId, Signature, Message)> {
+        let signed: SignedMessage = serde_json::from_slice(bytes)?;
+        signed.from.verify(&signed.data, &signed.signature)?;
+        let message = Message::from_bytes(&signed.data)?;
+        if message.from() != signed.from {
+            anyhow::bail!("message `from` does not match the signing key");
+        }
+        Ok((signed.from, signed.signature, message))
+    }
+}
+
+/// Reconstruct an ed25519 signature from its 64-byte on-disk form.
+fn signature_from_bytes(bytes: &[u8]) -> Result<Signature> {
+    let bytes: [u8; 64] = bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("signature must be 64 bytes"))?;
+    Ok(Signature::from_bytes(&bytes))
 }
\ No newline at end of file